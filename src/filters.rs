@@ -0,0 +1,94 @@
+//! 构建账户过滤条件的小型 builder API，在 `account`/`owner` 之外补上
+//! Geyser 支持的 `memcmp` 和 `datasize` 匹配，常用于按固定偏移量匹配
+//! SPL 代币账户的 mint 字节，而不必逐个枚举账户地址。
+
+use yellowstone_grpc_proto::geyser::{
+    subscribe_request_filter_accounts_filter::Filter as ProtoFilter,
+    subscribe_request_filter_accounts_filter_memcmp::Data as ProtoMemcmpData,
+    AccountFilter, SubscribeRequestFilterAccountsFilter,
+    SubscribeRequestFilterAccountsFilterMemcmp,
+};
+
+/// 一条 `memcmp`/`datasize` 过滤条件，`Memcmp` 按字节偏移比较，
+/// `DataSize` 只匹配账户数据的长度。
+#[derive(Debug, Clone)]
+pub enum AccountDataFilter {
+    Memcmp { offset: u64, data: MemcmpData },
+    DataSize(u64),
+}
+
+/// `memcmp` 里要比较的数据，原始字节或者 base58/base64 编码的字符串。
+#[derive(Debug, Clone)]
+pub enum MemcmpData {
+    Bytes(Vec<u8>),
+    Base58(String),
+    Base64(String),
+}
+
+/// 账户过滤条件的 builder：在 `account`/`owner` 列表之外还可以附加
+/// `memcmp`/`datasize` 条件，最终通过 [`AccountFilterSpec::build`] 降级
+/// 成 proto 层的 `AccountFilter`。
+#[derive(Debug, Clone, Default)]
+pub struct AccountFilterSpec {
+    pub account: Vec<String>,
+    pub owner: Vec<String>,
+    pub filters: Vec<AccountDataFilter>,
+}
+
+impl AccountFilterSpec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_account(mut self, pubkey: impl Into<String>) -> Self {
+        self.account.push(pubkey.into());
+        self
+    }
+
+    pub fn with_owner(mut self, owner: impl Into<String>) -> Self {
+        self.owner.push(owner.into());
+        self
+    }
+
+    pub fn with_memcmp(mut self, offset: u64, data: MemcmpData) -> Self {
+        self.filters.push(AccountDataFilter::Memcmp { offset, data });
+        self
+    }
+
+    pub fn with_datasize(mut self, size: u64) -> Self {
+        self.filters.push(AccountDataFilter::DataSize(size));
+        self
+    }
+
+    pub fn build(self) -> AccountFilter {
+        AccountFilter {
+            account: self.account,
+            owner: self.owner,
+            filters: self.filters.into_iter().map(lower_filter).collect(),
+        }
+    }
+}
+
+fn lower_filter(filter: AccountDataFilter) -> SubscribeRequestFilterAccountsFilter {
+    let filter = match filter {
+        AccountDataFilter::Memcmp { offset, data } => {
+            ProtoFilter::Memcmp(SubscribeRequestFilterAccountsFilterMemcmp {
+                offset,
+                data: Some(lower_memcmp_data(data)),
+            })
+        }
+        AccountDataFilter::DataSize(size) => ProtoFilter::Datasize(size),
+    };
+
+    SubscribeRequestFilterAccountsFilter {
+        filter: Some(filter),
+    }
+}
+
+fn lower_memcmp_data(data: MemcmpData) -> ProtoMemcmpData {
+    match data {
+        MemcmpData::Bytes(bytes) => ProtoMemcmpData::Bytes(bytes),
+        MemcmpData::Base58(s) => ProtoMemcmpData::Base58(s),
+        MemcmpData::Base64(s) => ProtoMemcmpData::Base64(s),
+    }
+}
@@ -1,19 +1,47 @@
-use anyhow::{Context, Result};
-use std::time::Duration;
-use tokio::time::interval;
+mod filters;
+mod multiplex;
+mod reconnect;
+mod sink;
+mod subscription;
+mod verify;
+
+use std::pin::Pin;
+use std::sync::Arc;
+
+use anyhow::Result;
+use futures::{Stream, StreamExt};
+use multiplex::{create_multiplexed_stream, GrpcSourceConfig};
+use reconnect::{create_geyser_reconnecting_stream_with_updates, GeyserConnectionConfig};
+use sink::{NdjsonSink, UpdateRecord, UpdateSink};
+use subscription::SubscriptionManager;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::sync::mpsc;
 use tracing::{info, warn};
-use yellowstone_grpc_client::GeyserGrpcClient;
+use verify::{spawn_verification_task, GrpcAccountSnapshot, VerifyConfig};
 use yellowstone_grpc_proto::geyser::{
     subscribe_request::{Accounts, Transactions},
-    CommitmentLevel, SubscribeRequest,
+    CommitmentLevel, SubscribeRequest, SubscribeUpdate,
 };
 
 
 // 配置信息 - 你需要修改这里的内容
 const GRPC_ENDPOINT: &str = "https://api.rpcpool.com:443";
+const RPC_ENDPOINT: &str = "https://api.mainnet-beta.solana.com";
 
 const AUTH_TOKEN: &str = "token";
-const PING_INTERVAL_SECS: u64 = 30; // 每30秒发送一次心跳保持连接
+
+// 是否开启 gRPC vs RPC 的一致性校验模式，默认关闭，按需打开。
+const ENABLE_VERIFICATION: bool = false;
+
+// NDJSON 记录里是否带上账户原始数据的 base64 编码（体积较大，默认关闭）。
+const INCLUDE_ACCOUNT_DATA: bool = false;
+
+// 是否同时订阅多个冗余 gRPC 端点，取最快到达的那份、丢弃重复/过期消息。
+// 关闭时走单一端点的重连流；打开后下面的 GRPC_ENDPOINT_BACKUP 也会被用上。
+// 开启多路复用时，运行期间增删过滤条件（SubscriptionManager）暂不支持，
+// 因为一次重发只能更新其中一个底层连接。
+const ENABLE_MULTIPLEXING: bool = false;
+const GRPC_ENDPOINT_BACKUP: &str = "https://api.rpcpool-backup.com:443";
 
 
 #[tokio::main]
@@ -22,19 +50,8 @@ async fn main() -> Result<()> {
     tracing_subscriber::fmt::init();
     info!("Solana gRPC 监听程序启动");
 
-    // 连接到 gRPC 服务器
-    info!("正在连接到 {}", GRPC_ENDPOINT);
-    let mut client = GeyserGrpcClient::connect(GRPC_ENDPOINT)
-        .await
-        .context("连接服务器失败")?;
-
-    // 设置认证信息
-    client.set_auth_token(AUTH_TOKEN);
-
-    // 创建数据订阅流
-    info!("正在创建数据订阅...");
-    let (mut sender, mut receiver) = client.subscribe().await
-        .context("创建订阅失败")?;
+    let connection_config =
+        GeyserConnectionConfig::new(GRPC_ENDPOINT).with_auth_token(AUTH_TOKEN);
 
     // 定义我们想要监听的数据类型和条件
     let subscribe_request = SubscribeRequest {
@@ -68,37 +85,58 @@ async fn main() -> Result<()> {
         ..Default::default()
     };
 
-    // 发送订阅请求
-    sender.send(subscribe_request).await
-        .context("发送订阅请求失败")?;
-    info!("订阅请求已发送，开始接收数据...");
+    let mut updates: Pin<Box<dyn Stream<Item = SubscribeUpdate>>> = if ENABLE_MULTIPLEXING {
+        // 同时连接主、备两个端点，谁先送到就先转发，由
+        // create_multiplexed_stream 按 (实体, slot) 去重。
+        let sources = vec![
+            GrpcSourceConfig {
+                connection: GeyserConnectionConfig::new(GRPC_ENDPOINT).with_auth_token(AUTH_TOKEN),
+                subscribe_request: subscribe_request.clone(),
+            },
+            GrpcSourceConfig {
+                connection: GeyserConnectionConfig::new(GRPC_ENDPOINT_BACKUP)
+                    .with_auth_token(AUTH_TOKEN),
+                subscribe_request: subscribe_request.clone(),
+            },
+        ];
+        Box::pin(create_multiplexed_stream(sources, extract_entity_and_seq))
+    } else {
+        // SubscriptionManager 持有 update_rx 的发送端，运行期间可以随时
+        // 增删过滤条件；重连流收到新的 SubscribeRequest 后会原地重发，
+        // 不用断线。
+        let (update_tx, update_rx) = mpsc::unbounded_channel();
+        let subscription_manager =
+            Arc::new(SubscriptionManager::new(subscribe_request.clone(), update_tx));
+        spawn_subscription_control(subscription_manager);
 
-    // 启动心跳任务，定期发送信号保持连接
-    let mut ping_interval = interval(Duration::from_secs(PING_INTERVAL_SECS));
-    let mut sender_clone = sender.clone();
-    tokio::spawn(async move {
-        loop {
-            ping_interval.tick().await;
-            let ping_request = SubscribeRequest {
-                ping: Some(yellowstone_grpc_proto::geyser::Ping {
-                    id: std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs() as u64,
-                }),
-                ..Default::default()
-            };
-            
-            if let Err(e) = sender_clone.send(ping_request).await {
-                warn!("发送心跳失败: {}", e);
-                break;
-            }
-            info!("发送了心跳信号，保持连接活跃");
-        }
-    });
+        Box::pin(create_geyser_reconnecting_stream_with_updates(
+            connection_config,
+            subscribe_request,
+            update_rx,
+        ))
+    };
+
+    // 可选的一致性校验模式：在后台把 gRPC 账户更新和 RPC getAccountInfo
+    // 的结果做比对，正常运行时不开启。只有在 watched_accounts 里的账户
+    // 才会被解析并送进校验队列，避免热门过滤条件（比如 chunk0-4 里按
+    // mint 匹配的一大批 SPL 账户）把少量真正关心的 sysvar 更新挤掉。
+    let verification = if ENABLE_VERIFICATION {
+        let config = VerifyConfig::new(RPC_ENDPOINT);
+        let watched_accounts = config.watched_accounts.clone();
+        let (tx, rx) = mpsc::channel(256);
+        spawn_verification_task(config, rx);
+        Some((tx, watched_accounts))
+    } else {
+        None
+    };
+
+    // 可插拔的输出目的地：解析出来的更新既打到 stdout 的人类可读日志，
+    // 也各写一行 NDJSON，方便下游管道消费。
+    let sinks: Vec<Box<dyn UpdateSink>> = vec![Box::new(NdjsonSink::stdout())];
+    let commitment = CommitmentLevel::Confirmed as i32;
 
     // 循环接收并处理数据
-    while let Some(msg) = receiver.message().await.context("接收数据失败")? {
+    while let Some(msg) = updates.next().await {
         // 处理账户更新数据
         if let Some(accounts) = msg.accounts {
             for account in accounts.accounts {
@@ -106,6 +144,33 @@ async fn main() -> Result<()> {
                 info!("账户地址: {}", account.pubkey);
                 info!("所在区块: {}", account.slot);
                 info!("数据长度: {} 字节", account.data.len());
+
+                if let Some((tx, watched_accounts)) = &verification {
+                    // 先按 pubkey 过滤，只有关心的账户才值得解析和入队。
+                    if watched_accounts.iter().any(|w| w == &account.pubkey) {
+                        match GrpcAccountSnapshot::from_account_info(account.slot, &account) {
+                            Ok(snapshot) => {
+                                if tx.try_send(snapshot).is_err() {
+                                    warn!("校验任务队列已满，丢弃这条账户更新");
+                                }
+                            }
+                            Err(err) => warn!("无法解析账户更新用于校验: {err}"),
+                        }
+                    }
+                }
+
+                let record = UpdateRecord::account(
+                    account.slot,
+                    commitment,
+                    account.pubkey.clone(),
+                    account.lamports,
+                    account.owner.clone(),
+                    &account.data,
+                    INCLUDE_ACCOUNT_DATA,
+                );
+                for sink in &sinks {
+                    sink.on_account(record.clone());
+                }
             }
         }
 
@@ -117,6 +182,11 @@ async fn main() -> Result<()> {
                 info!("所在区块: {}", tx.slot);
                 info!("是否成功: {}", tx.success);
                 info!("交易费用: {}", tx.fee);
+
+                let record = UpdateRecord::transaction(tx.slot, commitment, tx.signature, tx.fee, tx.success);
+                for sink in &sinks {
+                    sink.on_transaction(record.clone());
+                }
             }
         }
 
@@ -127,6 +197,11 @@ async fn main() -> Result<()> {
                 info!("插槽编号: {}", slot.slot);
                 info!("父插槽: {}", slot.parent);
                 info!("状态: {:?}", slot.status);
+
+                let record = UpdateRecord::slot(slot.slot, commitment, format!("{:?}", slot.status));
+                for sink in &sinks {
+                    sink.on_slot(record.clone());
+                }
             }
         }
 
@@ -139,3 +214,55 @@ async fn main() -> Result<()> {
     info!("结束");
     Ok(())
 }
+
+/// 给 [`create_multiplexed_stream`] 用的去重键提取函数：账户更新按
+/// pubkey 分实体，交易按签名分实体，slot 更新共用同一个实体，各自的
+/// slot 号充当单调序号。整条消息原样转发，去重只影响要不要丢弃。
+fn extract_entity_and_seq(update: SubscribeUpdate) -> Option<(String, u64, SubscribeUpdate)> {
+    let key = if let Some(accounts) = &update.accounts {
+        accounts
+            .accounts
+            .first()
+            .map(|account| (account.pubkey.clone(), account.slot))
+    } else if let Some(transactions) = &update.transactions {
+        transactions
+            .transactions
+            .first()
+            .map(|tx| (tx.signature.clone(), tx.slot))
+    } else if let Some(slots) = &update.slots {
+        slots.slots.first().map(|slot| ("slots".to_string(), slot.slot))
+    } else {
+        None
+    };
+
+    key.map(|(entity, seq)| (entity, seq, update))
+}
+
+/// 从标准输入读取控制命令，让运行期间也能增删过滤条件：
+/// `sub account <pubkey>`、`sub program <owner>`、`unsub <name>`。
+/// 这是最简单的"外部代码可以在运行时操纵订阅"的演示，真正部署时可以
+/// 换成 HTTP/IPC 之类的控制面。
+fn spawn_subscription_control(manager: Arc<SubscriptionManager>) {
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(tokio::io::stdin()).lines();
+
+        while let Ok(Some(line)) = lines.next_line().await {
+            let mut parts = line.split_whitespace();
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some("sub"), Some("account"), Some(pubkey)) => {
+                    info!("订阅账户: {pubkey}");
+                    manager.subscribe_account(pubkey);
+                }
+                (Some("sub"), Some("program"), Some(owner)) => {
+                    info!("订阅程序: {owner}");
+                    manager.subscribe_program(owner);
+                }
+                (Some("unsub"), Some(name), None) => {
+                    info!("取消订阅: {name}");
+                    manager.unsubscribe(name);
+                }
+                _ => warn!("无法识别的订阅命令: {line}"),
+            }
+        }
+    });
+}
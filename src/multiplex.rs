@@ -0,0 +1,101 @@
+//! 将多个（可能冗余的）gRPC 数据源合并成一条有序、去重的输出流：
+//! 每个来源各自通过 [`crate::reconnect::create_geyser_reconnecting_stream`]
+//! 重连，谁先送到就先转发，重复或过期的消息会被丢弃。
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use futures::stream::{select_all, Stream, StreamExt};
+use tracing::debug;
+use yellowstone_grpc_proto::geyser::{SubscribeRequest, SubscribeUpdate};
+
+use crate::reconnect::{create_geyser_reconnecting_stream, GeyserConnectionConfig};
+
+/// 一个冗余数据源的连接配置，多个来源可以指向不同的 RPC 提供商。
+#[derive(Debug, Clone)]
+pub struct GrpcSourceConfig {
+    pub connection: GeyserConnectionConfig,
+    pub subscribe_request: SubscribeRequest,
+}
+
+/// 多路合并一组数据源，按 `extractor` 提取的实体键 + 单调递增序号去重后
+/// 转发。
+///
+/// `extractor` 从一条 `SubscribeUpdate` 中取出 `(entity, seq, payload)`：
+/// `entity` 标识这条更新属于哪个逻辑实体（例如账户 pubkey），`seq` 是该
+/// 实体内部单调递增的序号（例如 slot）。不同实体的序号互不比较 ——
+/// 账户 B 的 slot 99 不会因为账户 A 已经转发过 slot 100 而被当成过期
+/// 丢弃。只做整条流排序去重（没有实体概念，比如纯 slot 更新）时，把
+/// `entity` 固定成同一个值（如 `()`）即可退化成全局单调去重。返回
+/// `None` 表示这条消息与去重逻辑无关，直接丢弃（例如 ping/pong）。
+pub fn create_multiplexed_stream<E, S, T, F>(
+    sources: Vec<GrpcSourceConfig>,
+    extractor: F,
+) -> impl Stream<Item = T>
+where
+    E: Eq + Hash + Clone,
+    S: Ord + Clone,
+    F: Fn(SubscribeUpdate) -> Option<(E, S, T)> + 'static,
+{
+    let streams = sources.into_iter().map(|source| {
+        create_geyser_reconnecting_stream(source.connection, source.subscribe_request).boxed()
+    });
+
+    let extracted = select_all(streams).filter_map(move |update| {
+        let result = extractor(update);
+        async move { result }
+    });
+
+    dedup_by_entity(extracted)
+}
+
+/// 按实体对 `(entity, seq, payload)` 流去重：每个实体独立维护自己最后
+/// 转发过的 `seq`，新消息的 `seq` 小于等于该实体记录的最后一个值就被
+/// 丢弃，否则转发并更新记录。
+fn dedup_by_entity<E, S, T>(stream: impl Stream<Item = (E, S, T)>) -> impl Stream<Item = T>
+where
+    E: Eq + Hash + Clone,
+    S: Ord + Clone,
+{
+    let mut last_seen: HashMap<E, S> = HashMap::new();
+
+    stream.filter_map(move |(entity, seq, payload)| {
+        let is_stale = last_seen
+            .get(&entity)
+            .map(|last| seq <= *last)
+            .unwrap_or(false);
+
+        let result = if is_stale {
+            debug!("丢弃重复或过期的消息");
+            None
+        } else {
+            last_seen.insert(entity, seq);
+            Some(payload)
+        };
+
+        async move { result }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::stream;
+
+    #[tokio::test]
+    async fn per_entity_dedup_does_not_cross_contaminate() {
+        // 账户 A 先转发到 slot 100，随后账户 B 在 slot 99 的更新依然应该
+        // 被转发 —— 它们是不同的实体，不能用同一个全局序号比较。
+        let input = vec![
+            ("a", 100u64, "a@100"),
+            ("b", 99u64, "b@99"),
+            ("a", 100u64, "a@100-dup"), // 同一实体的重复 seq，应该被丢弃
+            ("b", 100u64, "b@100"),
+            ("a", 99u64, "a@99-late"), // 同一实体的过期 seq，应该被丢弃
+        ];
+
+        let output: Vec<_> = dedup_by_entity(stream::iter(input)).collect().await;
+
+        assert_eq!(output, vec!["a@100", "b@99", "b@100"]);
+    }
+}
@@ -0,0 +1,217 @@
+//! 自动重连的订阅流：将"连接 -> 订阅 -> 接收"的循环封装成一个状态机，
+//! 在遇到传输错误或服务端关闭流时自动退避重试，而不是让整个程序退出。
+
+use std::time::Duration;
+
+use async_stream::stream;
+use futures::Stream;
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+use yellowstone_grpc_client::GeyserGrpcClient;
+use yellowstone_grpc_proto::geyser::{SubscribeRequest, SubscribeUpdate};
+
+/// 建立 gRPC 连接时用到的参数，替代原先写死在 `main` 里的常量。
+#[derive(Debug, Clone)]
+pub struct GeyserConnectionConfig {
+    pub endpoint: String,
+    pub auth_token: Option<String>,
+    pub connect_timeout: Duration,
+    pub request_timeout: Duration,
+    pub keepalive_interval: Duration,
+    pub keepalive_timeout: Duration,
+}
+
+impl GeyserConnectionConfig {
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            auth_token: None,
+            connect_timeout: Duration::from_secs(10),
+            request_timeout: Duration::from_secs(10),
+            keepalive_interval: Duration::from_secs(30),
+            keepalive_timeout: Duration::from_secs(10),
+        }
+    }
+
+    pub fn with_auth_token(mut self, token: impl Into<String>) -> Self {
+        self.auth_token = Some(token.into());
+        self
+    }
+}
+
+/// 退避参数：初始延迟、每次翻倍，直到封顶，成功收到一条消息后重置。
+#[derive(Debug, Clone, Copy)]
+pub struct BackoffConfig {
+    pub initial: Duration,
+    pub max: Duration,
+    pub multiplier: f64,
+}
+
+impl Default for BackoffConfig {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_secs(1),
+            max: Duration::from_secs(30),
+            multiplier: 2.0,
+        }
+    }
+}
+
+impl BackoffConfig {
+    fn next(&self, current: Duration) -> Duration {
+        let doubled = current.mul_f64(self.multiplier);
+        doubled.min(self.max)
+    }
+}
+
+/// 连接生命周期的状态机，仅用于日志和可观测性，不对外暴露细节。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConnectionState {
+    NotConnected,
+    Connecting,
+    Connected,
+    Recovering,
+}
+
+/// 创建一个会自动重连的订阅流：每当 `connect`、`subscribe` 或
+/// `receiver.message()` 出错时，记录日志、丢弃旧客户端、按退避延迟等待，
+/// 然后用同一个 `subscribe_request` 重新订阅。
+pub fn create_geyser_reconnecting_stream(
+    config: GeyserConnectionConfig,
+    subscribe_request: SubscribeRequest,
+) -> impl Stream<Item = SubscribeUpdate> {
+    let (_tx, rx) = mpsc::unbounded_channel();
+    create_geyser_reconnecting_stream_with_updates(config, subscribe_request, rx)
+}
+
+/// 与 [`create_geyser_reconnecting_stream`] 相同，但额外接受一个
+/// `update_rx`：每当 `crate::subscription::SubscriptionManager` 这样的
+/// 调用方往里面发送新的 `SubscribeRequest`，当前连接会立即用它替换旧的
+/// 过滤条件，而不需要重新建立连接；断线重连后也会使用最新收到的那一份。
+pub fn create_geyser_reconnecting_stream_with_updates(
+    config: GeyserConnectionConfig,
+    subscribe_request: SubscribeRequest,
+    mut update_rx: mpsc::UnboundedReceiver<SubscribeRequest>,
+) -> impl Stream<Item = SubscribeUpdate> {
+    stream! {
+        let backoff_config = BackoffConfig::default();
+        let mut backoff = backoff_config.initial;
+        let mut state = ConnectionState::NotConnected;
+        let mut subscribe_request = subscribe_request;
+
+        loop {
+            state = ConnectionState::Connecting;
+            info!("正在连接到 {}", config.endpoint);
+
+            let builder = GeyserGrpcClient::build_from_shared(config.endpoint.clone())
+                .and_then(|b| b.connect_timeout(config.connect_timeout))
+                .and_then(|b| b.timeout(config.request_timeout))
+                .and_then(|b| b.keep_alive_interval(config.keepalive_interval))
+                .and_then(|b| b.keep_alive_timeout(config.keepalive_timeout))
+                .and_then(|b| b.keep_alive_while_idle(true));
+
+            let mut client = match builder {
+                Ok(builder) => match builder.connect().await {
+                    Ok(mut client) => {
+                        if let Some(token) = &config.auth_token {
+                            client.set_auth_token(token.clone());
+                        }
+                        client
+                    }
+                    Err(err) => {
+                        error!("连接服务器失败: {err}");
+                        state = ConnectionState::Recovering;
+                        tokio::time::sleep(backoff).await;
+                        backoff = backoff_config.next(backoff);
+                        continue;
+                    }
+                },
+                Err(err) => {
+                    error!("构建连接失败: {err}");
+                    state = ConnectionState::Recovering;
+                    tokio::time::sleep(backoff).await;
+                    backoff = backoff_config.next(backoff);
+                    continue;
+                }
+            };
+
+            let (mut sender, mut receiver) = match client.subscribe().await {
+                Ok(pair) => pair,
+                Err(err) => {
+                    error!("创建订阅失败: {err}");
+                    state = ConnectionState::Recovering;
+                    tokio::time::sleep(backoff).await;
+                    backoff = backoff_config.next(backoff);
+                    continue;
+                }
+            };
+
+            if let Err(err) = sender.send(subscribe_request.clone()).await {
+                error!("发送订阅请求失败: {err}");
+                state = ConnectionState::Recovering;
+                tokio::time::sleep(backoff).await;
+                backoff = backoff_config.next(backoff);
+                continue;
+            }
+
+            state = ConnectionState::Connected;
+            info!("订阅请求已发送，开始接收数据...");
+
+            // 按 keepalive_interval 发送应用层心跳，防止过滤条件很安静时
+            // 中间的 LB/代理因为长时间没有数据而主动断开连接。
+            let mut ping_interval = tokio::time::interval(config.keepalive_interval);
+            ping_interval.tick().await; // 第一次 tick 立即完成，跳过它
+
+            'recv: loop {
+                tokio::select! {
+                    msg = receiver.message() => {
+                        match msg {
+                            Ok(Some(msg)) => {
+                                // 成功收到消息后重置退避延迟。
+                                backoff = backoff_config.initial;
+                                yield msg;
+                            }
+                            Ok(None) => {
+                                warn!("服务端关闭了数据流，准备重连");
+                                break 'recv;
+                            }
+                            Err(err) => {
+                                warn!("接收数据失败: {err}，准备重连");
+                                break 'recv;
+                            }
+                        }
+                    }
+                    Some(new_request) = update_rx.recv() => {
+                        subscribe_request = new_request;
+                        if let Err(err) = sender.send(subscribe_request.clone()).await {
+                            warn!("更新订阅条件失败: {err}，准备重连");
+                            break 'recv;
+                        }
+                        info!("已应用更新后的订阅条件");
+                    }
+                    _ = ping_interval.tick() => {
+                        let ping_request = SubscribeRequest {
+                            ping: Some(yellowstone_grpc_proto::geyser::Ping {
+                                id: std::time::SystemTime::now()
+                                    .duration_since(std::time::UNIX_EPOCH)
+                                    .unwrap_or_default()
+                                    .as_secs() as u64,
+                            }),
+                            ..Default::default()
+                        };
+
+                        if let Err(err) = sender.send(ping_request).await {
+                            warn!("发送心跳失败: {err}，准备重连");
+                            break 'recv;
+                        }
+                    }
+                }
+            }
+
+            state = ConnectionState::Recovering;
+            info!("{state:?}，{backoff:?} 后重试");
+            tokio::time::sleep(backoff).await;
+            backoff = backoff_config.next(backoff);
+        }
+    }
+}
@@ -0,0 +1,206 @@
+//! 把接收循环里原本只会 `info!` 一下的更新，改成可插拔的输出目的地：
+//! `UpdateSink` 把传输层和"怎么展示/落地这些数据"解耦，实现可以是打到
+//! stdout 或追加写入单个文件的 NDJSON（文件不做滚动切分），或者进程内的
+//! broadcast channel。
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+/// 一条更新的种类，用来在 NDJSON 里标出这是账户、交易还是 slot 更新。
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateKind {
+    Account,
+    Transaction,
+    Slot,
+}
+
+/// 解析、解码之后的统一记录格式，三种更新共用同一个 envelope，
+/// 具体字段按 `kind` 部分填充，其余留空。
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateRecord {
+    pub kind: UpdateKind,
+    pub slot: u64,
+    pub commitment: i32,
+    /// 账户 pubkey、交易签名，或者 slot 更新的状态（`{:?}` 格式化），看 `kind` 而定。
+    pub key: String,
+    pub lamports: Option<u64>,
+    pub owner: Option<String>,
+    /// 账户数据，按需 base64 编码；交易/slot 更新不填这个字段。
+    pub data_base64: Option<String>,
+    pub fee: Option<u64>,
+    pub success: Option<bool>,
+}
+
+impl UpdateRecord {
+    pub fn account(
+        slot: u64,
+        commitment: i32,
+        pubkey: String,
+        lamports: u64,
+        owner: String,
+        data: &[u8],
+        include_data: bool,
+    ) -> Self {
+        Self {
+            kind: UpdateKind::Account,
+            slot,
+            commitment,
+            key: pubkey,
+            lamports: Some(lamports),
+            owner: Some(owner),
+            data_base64: include_data.then(|| base64_encode(data)),
+            fee: None,
+            success: None,
+        }
+    }
+
+    pub fn transaction(
+        slot: u64,
+        commitment: i32,
+        signature: String,
+        fee: u64,
+        success: bool,
+    ) -> Self {
+        Self {
+            kind: UpdateKind::Transaction,
+            slot,
+            commitment,
+            key: signature,
+            lamports: None,
+            owner: None,
+            data_base64: None,
+            fee: Some(fee),
+            success: Some(success),
+        }
+    }
+
+    pub fn slot(slot: u64, commitment: i32, status: String) -> Self {
+        Self {
+            kind: UpdateKind::Slot,
+            slot,
+            commitment,
+            key: status,
+            lamports: None,
+            owner: None,
+            data_base64: None,
+            fee: None,
+            success: None,
+        }
+    }
+}
+
+fn base64_encode(data: &[u8]) -> String {
+    use base64::Engine;
+    base64::engine::general_purpose::STANDARD.encode(data)
+}
+
+/// 解耦"收到什么更新"和"这条更新该怎么展示/落地"的接口。
+pub trait UpdateSink: Send + Sync {
+    fn on_account(&self, record: UpdateRecord);
+    fn on_transaction(&self, record: UpdateRecord);
+    fn on_slot(&self, record: UpdateRecord);
+}
+
+/// 把每条记录序列化成一行 JSON（NDJSON），写到任意实现了 `Write` 的目的地，
+/// 比如 stdout 或者打开的文件。
+pub struct NdjsonSink<W: Write + Send> {
+    writer: Mutex<W>,
+}
+
+impl<W: Write + Send> NdjsonSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: Mutex::new(writer),
+        }
+    }
+
+    fn write_record(&self, record: UpdateRecord) {
+        let line = match serde_json::to_string(&record) {
+            Ok(line) => line,
+            Err(err) => {
+                tracing::warn!("序列化更新记录失败: {err}");
+                return;
+            }
+        };
+
+        let mut writer = self.writer.lock().unwrap();
+        if let Err(err) = writeln!(writer, "{line}") {
+            tracing::warn!("写入 NDJSON 记录失败: {err}");
+        }
+    }
+}
+
+impl NdjsonSink<std::fs::File> {
+    /// 打开（或新建）一个文件用于追加写入 NDJSON 记录。
+    pub fn open_file(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("打开 NDJSON 输出文件失败: {}", path.display()))?;
+        Ok(Self::new(file))
+    }
+}
+
+impl NdjsonSink<std::io::Stdout> {
+    pub fn stdout() -> Self {
+        Self::new(std::io::stdout())
+    }
+}
+
+impl<W: Write + Send> UpdateSink for NdjsonSink<W> {
+    fn on_account(&self, record: UpdateRecord) {
+        self.write_record(record);
+    }
+
+    fn on_transaction(&self, record: UpdateRecord) {
+        self.write_record(record);
+    }
+
+    fn on_slot(&self, record: UpdateRecord) {
+        self.write_record(record);
+    }
+}
+
+/// 进程内的 broadcast 扇出：同一个二进制里的多个消费者都可以各自
+/// `subscribe()` 一份接收端，互不影响。
+pub struct BroadcastSink {
+    tx: broadcast::Sender<UpdateRecord>,
+}
+
+impl BroadcastSink {
+    pub fn new(capacity: usize) -> Self {
+        let (tx, _rx) = broadcast::channel(capacity);
+        Self { tx }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<UpdateRecord> {
+        self.tx.subscribe()
+    }
+
+    fn publish(&self, record: UpdateRecord) {
+        // 没有任何订阅者时 send 会返回错误，这是正常情况，忽略即可。
+        let _ = self.tx.send(record);
+    }
+}
+
+impl UpdateSink for BroadcastSink {
+    fn on_account(&self, record: UpdateRecord) {
+        self.publish(record);
+    }
+
+    fn on_transaction(&self, record: UpdateRecord) {
+        self.publish(record);
+    }
+
+    fn on_slot(&self, record: UpdateRecord) {
+        self.publish(record);
+    }
+}
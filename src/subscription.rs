@@ -0,0 +1,160 @@
+//! 允许在订阅流运行期间动态增删账户/程序过滤条件的管理器，
+//! 而不必像最初那样把 `SubscribeRequest` 写死一次就不再变化。
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use tracing::warn;
+use yellowstone_grpc_proto::geyser::{
+    subscribe_request::{Accounts, Transactions},
+    AccountFilter, SubscribeRequest, TransactionFilter,
+};
+
+use crate::filters::AccountFilterSpec;
+
+/// 多次快速调用在这个时间窗口内会被合并成一次 `SubscribeRequest` 重发。
+const DEBOUNCE_WINDOW: Duration = Duration::from_millis(200);
+
+/// 当前订阅的过滤条件集合，按名字索引，方便后续按名字移除。
+#[derive(Default)]
+struct FilterSet {
+    accounts: HashMap<String, AccountFilter>,
+    transactions: HashMap<String, TransactionFilter>,
+}
+
+impl FilterSet {
+    /// 用 `base_request` 里已经配置好的 accounts/transactions 过滤条件
+    /// 作为初始状态，这样第一次 `subscribe_account`/`subscribe_program`
+    /// 调用是往现有过滤条件上“加”，而不是把它们整个替换掉。
+    fn seeded_from(base: &SubscribeRequest) -> Self {
+        let mut set = Self::default();
+
+        if let Some(accounts) = &base.accounts {
+            for (i, filter) in accounts.filters.iter().enumerate() {
+                set.accounts.insert(format!("base-account-{i}"), filter.clone());
+            }
+        }
+        if let Some(transactions) = &base.transactions {
+            for (i, filter) in transactions.filters.iter().enumerate() {
+                set.transactions
+                    .insert(format!("base-transaction-{i}"), filter.clone());
+            }
+        }
+
+        set
+    }
+
+    fn to_subscribe_request(&self, base: &SubscribeRequest) -> SubscribeRequest {
+        let mut request = base.clone();
+        request.accounts = Some(Accounts {
+            filters: self.accounts.values().cloned().collect(),
+            ..Default::default()
+        });
+        request.transactions = Some(Transactions {
+            filters: self.transactions.values().cloned().collect(),
+            ..Default::default()
+        });
+        request
+    }
+}
+
+/// 在订阅流运行期间增删过滤条件：持有 `sender` 半边，重建完整的
+/// `SubscribeRequest` 后通过它重发，Yellowstone 会把收到的最新一份
+/// 当作当前生效的过滤集合。短时间内的多次调用会被合并成一次重发。
+pub struct SubscriptionManager {
+    base_request: Arc<SubscribeRequest>,
+    filters: Arc<Mutex<FilterSet>>,
+    debounce_tx: mpsc::Sender<()>,
+}
+
+impl SubscriptionManager {
+    /// `base_request` 提供除 accounts/transactions 以外的公共字段（例如
+    /// slots 订阅和 commitment 级别），`update_tx` 是
+    /// [`crate::reconnect::create_geyser_reconnecting_stream_with_updates`]
+    /// 使用的那个 `update_rx` 的发送端。
+    pub fn new(
+        base_request: SubscribeRequest,
+        update_tx: mpsc::UnboundedSender<SubscribeRequest>,
+    ) -> Self {
+        let filters = Arc::new(Mutex::new(FilterSet::seeded_from(&base_request)));
+        let base_request = Arc::new(base_request);
+        let debounce_tx = Self::spawn_debounce_task(base_request.clone(), filters.clone(), update_tx);
+
+        Self {
+            base_request,
+            filters,
+            debounce_tx,
+        }
+    }
+
+    /// 容量为 1 的 channel 加 `try_send`：debounce 窗口内涌入的多次触发
+    /// 只保留一个待处理标记，天然合并成一次重发。
+    fn spawn_debounce_task(
+        base_request: Arc<SubscribeRequest>,
+        filters: Arc<Mutex<FilterSet>>,
+        update_tx: mpsc::UnboundedSender<SubscribeRequest>,
+    ) -> mpsc::Sender<()> {
+        let (tx, mut rx) = mpsc::channel::<()>(1);
+
+        tokio::spawn(async move {
+            while rx.recv().await.is_some() {
+                tokio::time::sleep(DEBOUNCE_WINDOW).await;
+                // 窗口内又来的触发在这里直接清掉：下面构造请求时读的是最新的
+                // 过滤条件，已经包含了它们的改动，不会再单独重发。
+                while rx.try_recv().is_ok() {}
+
+                let request = {
+                    let filters = filters.lock().unwrap();
+                    filters.to_subscribe_request(&base_request)
+                };
+
+                if update_tx.send(request).is_err() {
+                    warn!("订阅流已经关闭，停止监听过滤条件变更");
+                    break;
+                }
+            }
+        });
+
+        tx
+    }
+
+    /// 订阅单个账户地址。
+    pub fn subscribe_account(&self, pubkey: impl Into<String>) {
+        let pubkey = pubkey.into();
+        self.subscribe_with_spec(pubkey.clone(), AccountFilterSpec::new().with_account(pubkey));
+    }
+
+    /// 订阅某个程序（owner）拥有的所有账户。
+    pub fn subscribe_program(&self, owner: impl Into<String>) {
+        let owner = owner.into();
+        let name = format!("program:{owner}");
+        self.subscribe_with_spec(name, AccountFilterSpec::new().with_owner(owner));
+    }
+
+    /// 用完整的 [`AccountFilterSpec`]（可以带 `memcmp`/`datasize` 条件）
+    /// 订阅，例如按固定偏移匹配某个 mint 的所有 SPL 代币账户。
+    pub fn subscribe_with_spec(&self, name: impl Into<String>, spec: AccountFilterSpec) {
+        {
+            let mut filters = self.filters.lock().unwrap();
+            filters.accounts.insert(name.into(), spec.build());
+        }
+        self.request_resend();
+    }
+
+    /// 按名字移除一个账户或程序过滤条件（`subscribe_account` 用账户地址
+    /// 本身作为名字，`subscribe_program` 用 `program:<owner>`）。
+    pub fn unsubscribe(&self, name: &str) {
+        {
+            let mut filters = self.filters.lock().unwrap();
+            filters.accounts.remove(name);
+        }
+        self.request_resend();
+    }
+
+    fn request_resend(&self) {
+        // 满了就说明已经有一次待处理的重发了，不需要再排队。
+        let _ = self.debounce_tx.try_send(());
+    }
+}
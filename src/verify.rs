@@ -0,0 +1,150 @@
+//! 可选的校验模式：把 gRPC 推送的账户更新和 RPC `getAccountInfo` 返回的
+//! 结果做比对，确认 Geyser 数据源没有漏报或者数据不一致。sysvar 账户
+//! （Clock、EpochSchedule、Rent、SlotHashes、StakeHistory 等）更新节奏
+//! 规律，默认拿来做校验目标比较合适。
+
+use std::str::FromStr;
+use std::time::Instant;
+
+use anyhow::Context;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::{CommitmentConfig, CommitmentLevel as RpcCommitmentLevel};
+use solana_sdk::pubkey::Pubkey;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+use yellowstone_grpc_proto::geyser::SubscribeUpdateAccountInfo;
+
+/// 校验模式需要的配置：一个 RPC 端点，以及要盯防的账户列表。
+#[derive(Debug, Clone)]
+pub struct VerifyConfig {
+    pub rpc_endpoint: String,
+    pub commitment: CommitmentConfig,
+    pub watched_accounts: Vec<String>,
+}
+
+impl VerifyConfig {
+    pub fn new(rpc_endpoint: impl Into<String>) -> Self {
+        Self {
+            rpc_endpoint: rpc_endpoint.into(),
+            commitment: CommitmentConfig {
+                commitment: RpcCommitmentLevel::Confirmed,
+            },
+            watched_accounts: default_sysvar_accounts(),
+        }
+    }
+}
+
+/// 一组更新节奏规律、适合用来验证 Geyser 数据源是否忠实的 sysvar 账户。
+pub fn default_sysvar_accounts() -> Vec<String> {
+    vec![
+        "SysvarC1ock11111111111111111111111111111111".to_string(),
+        "SysvarEpochSchedu1e111111111111111111111111".to_string(),
+        "SysvarRent111111111111111111111111111111111".to_string(),
+        "SysvarS1otHashes111111111111111111111111111".to_string(),
+        "SysvarStakeHistory1111111111111111111111111".to_string(),
+    ]
+}
+
+/// 一条待校验的 gRPC 账户更新，从接收循环的 accounts 分支里提取出来，
+/// 通过 channel 转交给后台校验任务，避免阻塞主接收循环。
+#[derive(Debug, Clone)]
+pub struct GrpcAccountSnapshot {
+    pub pubkey: String,
+    pub slot: u64,
+    pub lamports: u64,
+    pub owner: String,
+    pub data: Vec<u8>,
+    pub observed_at: Instant,
+}
+
+impl GrpcAccountSnapshot {
+    pub fn from_account_info(slot: u64, account: &SubscribeUpdateAccountInfo) -> anyhow::Result<Self> {
+        // account.pubkey/owner 和其余调用点（main.rs 的日志、chunk0-6 的
+        // UpdateRecord）一样是已经格式化好的 base58 字符串，这里只是顺手
+        // 校验一下它确实是合法公钥，不是重新从原始字节解码。
+        Pubkey::from_str(&account.pubkey)
+            .with_context(|| format!("account.pubkey 不是合法的公钥: {}", account.pubkey))?;
+        Pubkey::from_str(&account.owner)
+            .with_context(|| format!("account.owner 不是合法的公钥: {}", account.owner))?;
+
+        Ok(Self {
+            pubkey: account.pubkey.clone(),
+            slot,
+            lamports: account.lamports,
+            owner: account.owner.clone(),
+            data: account.data.clone(),
+            observed_at: Instant::now(),
+        })
+    }
+}
+
+/// 启动后台校验任务，消费 `rx` 里的 gRPC 账户快照并和 RPC 的
+/// `getAccountInfo` 比对，任何字段不一致都会记录下来，包含两者之间的
+/// 可见延迟。只对 `config.watched_accounts` 里的账户做这件事。
+pub fn spawn_verification_task(
+    config: VerifyConfig,
+    mut rx: mpsc::Receiver<GrpcAccountSnapshot>,
+) {
+    tokio::spawn(async move {
+        let rpc_client = RpcClient::new_with_commitment(config.rpc_endpoint.clone(), config.commitment);
+
+        while let Some(snapshot) = rx.recv().await {
+            if !config.watched_accounts.contains(&snapshot.pubkey) {
+                continue;
+            }
+
+            if let Err(err) = verify_account_snapshot(&rpc_client, &snapshot).await {
+                warn!("校验账户 {} 失败: {err}", snapshot.pubkey);
+            }
+        }
+    });
+}
+
+async fn verify_account_snapshot(
+    rpc_client: &RpcClient,
+    snapshot: &GrpcAccountSnapshot,
+) -> anyhow::Result<()> {
+    let pubkey: Pubkey = snapshot.pubkey.parse()?;
+    let fetch_started_at = Instant::now();
+    let account = rpc_client.get_account(&pubkey).await?;
+    let lag = fetch_started_at.duration_since(snapshot.observed_at);
+
+    let rpc_owner = account.owner.to_string();
+    let mut mismatches = Vec::new();
+
+    if account.lamports != snapshot.lamports {
+        mismatches.push(format!(
+            "lamports: grpc={} rpc={}",
+            snapshot.lamports, account.lamports
+        ));
+    }
+    if rpc_owner != snapshot.owner {
+        mismatches.push(format!("owner: grpc={} rpc={}", snapshot.owner, rpc_owner));
+    }
+    if account.data.len() != snapshot.data.len() {
+        mismatches.push(format!(
+            "data_len: grpc={} rpc={}",
+            snapshot.data.len(),
+            account.data.len()
+        ));
+    } else if account.data != snapshot.data {
+        mismatches.push("data: 字节不一致".to_string());
+    }
+
+    if mismatches.is_empty() {
+        info!(
+            "账户 {} 在 slot {} 校验一致（gRPC 比 RPC 早看到 {:?}）",
+            snapshot.pubkey, snapshot.slot, lag
+        );
+    } else {
+        warn!(
+            "账户 {} 在 slot {} 校验发现差异: {}（gRPC 领先 RPC {:?}）",
+            snapshot.pubkey,
+            snapshot.slot,
+            mismatches.join(", "),
+            lag
+        );
+    }
+
+    Ok(())
+}